@@ -0,0 +1,254 @@
+// src/config.rs
+//
+// Runtime configuration, loaded from `drctr.toml` (or the path named by the
+// `DRCTR_CONFIG` env var) if present, falling back to the compiled-in
+// defaults that used to be hardcoded in `main.rs` otherwise. This is what
+// lets an operator point the app at a different airframe - IP/ports, control
+// step size, key bindings - without a recompile.
+
+use std::{collections::HashMap, time::Duration};
+
+use sdl2::keyboard::Scancode;
+use serde::Deserialize;
+
+use crate::{ControlBits, DEFAULT_CTRL_BITS};
+
+const CONFIG_ENV_VAR: &str = "DRCTR_CONFIG";
+const DEFAULT_CONFIG_NAME: &str = "drctr";
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub network: NetworkConfig,
+    pub timing: TimingConfig,
+    pub video: VideoConfig,
+    pub controls: ControlsConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            network: NetworkConfig::default(),
+            timing: TimingConfig::default(),
+            video: VideoConfig::default(),
+            controls: ControlsConfig::default(),
+        }
+    }
+}
+
+impl Config {
+    /// Loads `drctr.toml` (or `$DRCTR_CONFIG`) from the current directory.
+    /// Falls back to the compiled-in defaults if the file is missing, and
+    /// (logging why) if it fails to parse.
+    pub fn load() -> Self {
+        let name = std::env::var(CONFIG_ENV_VAR).unwrap_or_else(|_| DEFAULT_CONFIG_NAME.to_owned());
+        let loaded = config::Config::builder()
+            .add_source(config::File::with_name(&name).required(false))
+            .build()
+            .and_then(|built| built.try_deserialize::<Config>());
+
+        match loaded {
+            Ok(cfg) => cfg,
+            Err(e) => {
+                eprintln!("[Config] Falling back to compiled-in defaults ({})", e);
+                Config::default()
+            }
+        }
+    }
+
+    /// Looks up the action bound to a key, if any.
+    pub fn action_for(&self, sc: Scancode) -> Option<Action> {
+        self.controls.bindings_by_scancode().get(&sc).copied()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct NetworkConfig {
+    pub source_addr: String,
+    pub dest_addr: String,
+    pub dest_tcp_addr: String,
+    pub dest_host: String,
+    pub local_host: String,
+    pub video_fwd_addr: String,
+    pub local_video_port: u16,
+    /// RTSP mount point, without the leading slash (e.g. `webcam`).
+    pub rtsp_path: String,
+    /// Set to also re-broadcast the video feed to a WHIP endpoint.
+    pub whip_endpoint: Option<String>,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            source_addr: "0.0.0.0:58737".to_owned(),
+            dest_addr: "192.168.1.1:7099".to_owned(),
+            dest_tcp_addr: "192.168.1.1:7070".to_owned(),
+            dest_host: "192.168.1.1".to_owned(),
+            local_host: "192.168.1.100".to_owned(),
+            video_fwd_addr: "127.0.0.1:9090".to_owned(),
+            local_video_port: 8768,
+            rtsp_path: "webcam".to_owned(),
+            whip_endpoint: None,
+        }
+    }
+}
+
+impl NetworkConfig {
+    pub fn rtsp_uri(&self) -> String {
+        format!("rtsp://{}/{}", self.dest_tcp_addr, self.rtsp_path)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct TimingConfig {
+    pub heartbeat_interval_ms: u64,
+    pub movement_command_ms: u64,
+}
+
+impl Default for TimingConfig {
+    fn default() -> Self {
+        Self {
+            heartbeat_interval_ms: 100,
+            movement_command_ms: 2000,
+        }
+    }
+}
+
+impl TimingConfig {
+    pub fn heartbeat_interval(&self) -> Duration {
+        Duration::from_millis(self.heartbeat_interval_ms)
+    }
+
+    pub fn movement_command_dur(&self) -> Duration {
+        Duration::from_millis(self.movement_command_ms)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct VideoConfig {
+    /// Display rotation, in degrees, matching `canvas.copy_ex`'s `angle`
+    /// parameter. The airframe's camera is mounted rotated, hence 90.
+    /// Recordings are muxed as received and are not rotated.
+    pub rotation_deg: f64,
+    /// Monospace TTF used to render the stats HUD. If this fails to load,
+    /// the HUD is disabled (with a logged warning) rather than failing the
+    /// whole UI.
+    pub hud_font_path: String,
+    /// Container extension for mid-flight recordings, e.g. `"mp4"` or
+    /// `"mkv"` - ffmpeg picks the muxer to use from this.
+    pub recording_extension: String,
+}
+
+impl Default for VideoConfig {
+    fn default() -> Self {
+        Self {
+            rotation_deg: 90.0,
+            hud_font_path: "/usr/share/fonts/truetype/dejavu/DejaVuSansMono.ttf".to_owned(),
+            recording_extension: "mp4".to_owned(),
+        }
+    }
+}
+
+/// What a key press does to the outgoing `ControlBits`.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Launch,
+    Land,
+    Aux,
+    Panic,
+    RollLeft,
+    RollRight,
+    ThrottleUp,
+    ThrottleDown,
+    YawLeft,
+    YawRight,
+    ToggleRecording,
+}
+
+impl Action {
+    /// Applies this action to `center`, offset by `step`. `ToggleRecording`
+    /// isn't a movement command, so it leaves `center` untouched; the caller
+    /// is expected to special-case it instead of sending the result.
+    pub fn apply(self, center: ControlBits, step: u8) -> ControlBits {
+        let mut bits = center;
+        match self {
+            Action::Launch => bits[4] = crate::CTRL_LAUNCH,
+            Action::Land => bits[4] = crate::CTRL_LAND,
+            Action::Aux => bits[4] = crate::CTRL_AUX,
+            Action::Panic => bits[4] = crate::CTRL_PANIC,
+            Action::RollLeft => bits[0] = bits[0].saturating_sub(step),
+            Action::RollRight => bits[0] = bits[0].saturating_add(step),
+            Action::ThrottleUp => bits[1] = bits[1].saturating_add(step),
+            Action::ThrottleDown => bits[1] = bits[1].saturating_sub(step),
+            Action::YawLeft => bits[3] = bits[3].saturating_add(step),
+            Action::YawRight => bits[3] = bits[3].saturating_sub(step),
+            Action::ToggleRecording => {}
+        }
+        bits
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct KeyBinding {
+    /// SDL scancode name, e.g. `"Space"`, `"Left"`, `"A"` - see `Scancode::from_name`.
+    pub key: String,
+    pub action: Action,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ControlsConfig {
+    /// Per-press step applied to roll/throttle/yaw bits.
+    pub step: u8,
+    /// Center (no input) value for each control byte.
+    pub center: ControlBits,
+    pub bindings: Vec<KeyBinding>,
+}
+
+impl Default for ControlsConfig {
+    fn default() -> Self {
+        Self {
+            step: 0x1d,
+            center: DEFAULT_CTRL_BITS,
+            bindings: vec![
+                binding("Space", Action::Launch),
+                binding("L", Action::Land),
+                binding("O", Action::Aux),
+                binding("P", Action::Panic),
+                binding("A", Action::RollLeft),
+                binding("Left", Action::RollLeft),
+                binding("D", Action::RollRight),
+                binding("Right", Action::RollRight),
+                binding("W", Action::ThrottleUp),
+                binding("Up", Action::ThrottleUp),
+                binding("S", Action::ThrottleDown),
+                binding("Down", Action::ThrottleDown),
+                binding("Q", Action::YawLeft),
+                binding("E", Action::YawRight),
+                binding("R", Action::ToggleRecording),
+            ],
+        }
+    }
+}
+
+fn binding(key: &str, action: Action) -> KeyBinding {
+    KeyBinding {
+        key: key.to_owned(),
+        action,
+    }
+}
+
+impl ControlsConfig {
+    /// Resolved on demand rather than cached: key lookups only happen from
+    /// the SDL event loop on an actual keypress, nowhere near a hot path.
+    fn bindings_by_scancode(&self) -> HashMap<Scancode, Action> {
+        self.bindings
+            .iter()
+            .filter_map(|b| Scancode::from_name(&b.key).map(|sc| (sc, b.action)))
+            .collect()
+    }
+}
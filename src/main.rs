@@ -1,10 +1,13 @@
 // src/main.rs
 
+pub mod config;
 pub mod handshake;
+pub mod recorder;
 pub mod video;
+pub mod webrtc;
 
 use std::{
-    io::{Read, Write},
+    io::Write,
     net::{TcpStream, UdpSocket},
     sync::{
         Arc,
@@ -15,22 +18,6 @@ use std::{
     time::{Duration, Instant},
 };
 
-use anyhow::Context;
-
-// --- Configuration ---
-mod config {
-    use std::time::Duration;
-    pub const SOURCE_ADDR: &str = "0.0.0.0:58737";
-    pub const DEST_ADDR: &str = "192.168.1.1:7099";
-    pub const DEST_TCP_ADDR: &str = "192.168.1.1:7070";
-    pub const DEST_HOST: &str = "192.168.1.1";
-    pub const LOCAL_HOST: &str = "192.168.1.100";
-    pub const VIDEO_FWD_ADDR: &str = "127.0.0.1:9090";
-    pub const LOCAL_VIDEO_PORT: u16 = 8768;
-    pub const HEARTBEAT_INTERVAL: Duration = Duration::from_millis(100);
-    pub const MOVEMENT_COMMAND_DUR: Duration = Duration::from_millis(2000);
-}
-
 pub type Heartbeat = [u8; 9];
 
 #[derive(Debug, Clone)]
@@ -75,6 +62,7 @@ fn run_network_loop(
     rx: Receiver<Command>,
     standby_payload: Heartbeat,
     running: Arc<AtomicBool>,
+    cfg: Arc<config::Config>,
 ) -> std::io::Result<()> {
     let mut current_command: Option<ActiveCommand> = None;
     let mut small_hb_time = Instant::now();
@@ -104,27 +92,29 @@ fn run_network_loop(
 
         // Send either active or standby
         if let Some(active) = &current_command {
-            socket.send_to(&active.payload, config::DEST_ADDR)?;
+            socket.send_to(&active.payload, &cfg.network.dest_addr)?;
         } else {
-            socket.send_to(&standby_payload, config::DEST_ADDR)?;
+            socket.send_to(&standby_payload, &cfg.network.dest_addr)?;
         }
 
         // Small heartbeat once a second
         if small_hb_time.elapsed() >= Duration::from_secs(1) {
-            socket.send_to(&[0x01, 0x01], config::DEST_ADDR)?;
+            socket.send_to(&[0x01, 0x01], &cfg.network.dest_addr)?;
             small_hb_time = Instant::now();
         }
 
         std::io::stdout().flush()?;
-        thread::sleep(config::HEARTBEAT_INTERVAL);
+        thread::sleep(cfg.timing.heartbeat_interval());
     }
     Ok(())
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cfg = Arc::new(config::Config::load());
+
     // Channel for Commands
     let (tx, rx): (Sender<Command>, Receiver<Command>) = mpsc::channel();
-    let standby_payload: Heartbeat = create_heartbeat(DEFAULT_CTRL_BITS);
+    let standby_payload: Heartbeat = create_heartbeat(cfg.controls.center);
 
     // Shared flag for shutdown
     let running = Arc::new(AtomicBool::new(true));
@@ -134,27 +124,30 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     })?;
 
     println!("Starting UDP packet sender…");
-    println!("--> Sending to {}", config::DEST_ADDR);
+    println!("--> Sending to {}", cfg.network.dest_addr);
 
     // Bind UDP & TCP for handshake
-    let socket = UdpSocket::bind(config::SOURCE_ADDR)?;
+    let socket = UdpSocket::bind(&cfg.network.source_addr)?;
     let mut tcp_stream =
-        TcpStream::connect_timeout(&config::DEST_TCP_ADDR.parse()?, Duration::from_secs(5))?;
+        TcpStream::connect_timeout(&cfg.network.dest_tcp_addr.parse()?, Duration::from_secs(5))?;
     tcp_stream.set_read_timeout(Some(Duration::from_secs(3)))?;
-    println!("[Handshake] TCP connected to {}", config::DEST_TCP_ADDR);
+    println!("[Handshake] TCP connected to {}", cfg.network.dest_tcp_addr);
+
+    handshake::perform(&mut tcp_stream, &cfg)?;
 
     // Spawn the network loop
     {
         let running_net = running.clone();
+        let cfg_net = cfg.clone();
         thread::spawn(move || {
-            if let Err(e) = run_network_loop(socket, rx, standby_payload, running_net) {
+            if let Err(e) = run_network_loop(socket, rx, standby_payload, running_net, cfg_net) {
                 eprintln!("[Network] Error: {}", e);
             }
         });
     }
 
     // Enter the SDL2 + video receiver loop, driving Command::send on keypress
-    video::run_video_receiver(tx, running)?;
+    video::run_video_receiver(tx, running, cfg)?;
 
     println!("\nShutting down.");
     Ok(())
@@ -0,0 +1,153 @@
+// src/recorder.rs
+//
+// Optional MP4/MKV recorder: muxes the raw RTSP stream straight through to a
+// local container (no re-decoding), as a parallel sink alongside rendering so
+// recording never stalls the display.
+
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use ffmpeg_next::{Packet, Rational, codec, format};
+
+/// Shared on/off switch: the UI thread flips this on keypress, the decode
+/// thread polls it once per packet and opens/closes the output file to match.
+#[derive(Clone)]
+pub struct RecorderToggle(Arc<AtomicBool>);
+
+impl RecorderToggle {
+    pub fn new() -> Self {
+        Self(Arc::new(AtomicBool::new(false)))
+    }
+
+    /// Flips the switch and returns the new state, so the caller can log it.
+    pub fn toggle(&self) -> bool {
+        !self.0.fetch_xor(true, Ordering::SeqCst)
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+struct OpenOutput {
+    octx: format::context::Output,
+    stream_index: usize,
+    in_time_base: Rational,
+    out_time_base: Rational,
+}
+
+/// Owns the (optional) open output file. Lives entirely on the decode thread;
+/// `feed` is called once per demuxed packet and is a no-op while recording is off.
+pub struct Recorder {
+    toggle: RecorderToggle,
+    /// Container extension, e.g. `"mp4"` or `"mkv"` - ffmpeg picks the muxer
+    /// to use from this when the output file is opened.
+    extension: String,
+    output: Option<OpenOutput>,
+    /// Set whenever a new output is opened; cleared the moment the first
+    /// keyframe after that arrives. While set, `feed` drops packets instead
+    /// of muxing them, so a file never opens on an undecodable P-frame.
+    waiting_for_keyframe: bool,
+}
+
+impl Recorder {
+    pub fn new(toggle: RecorderToggle, extension: String) -> Self {
+        Self {
+            toggle,
+            extension,
+            output: None,
+            waiting_for_keyframe: false,
+        }
+    }
+
+    /// Call once per demuxed video packet. Opens a new timestamped file the
+    /// moment recording is switched on, and flushes/closes it the moment it's
+    /// switched off.
+    pub fn feed(&mut self, packet: &Packet, params: &codec::Parameters, in_time_base: Rational) {
+        let enabled = self.toggle.is_enabled();
+        if enabled && self.output.is_none() {
+            match Self::open(params, in_time_base, &self.extension) {
+                Ok(out) => {
+                    self.output = Some(out);
+                    self.waiting_for_keyframe = true;
+                }
+                Err(e) => eprintln!("[Recorder] Failed to open output: {:?}", e),
+            }
+        } else if !enabled && self.output.is_some() {
+            self.close();
+        }
+
+        if enabled {
+            if self.waiting_for_keyframe {
+                if !packet.is_key() {
+                    return;
+                }
+                self.waiting_for_keyframe = false;
+            }
+            self.write(packet);
+        }
+    }
+
+    /// Flushes and closes any in-progress recording. Safe to call on shutdown
+    /// even if recording was never turned on.
+    pub fn shutdown(&mut self) {
+        self.close();
+    }
+
+    fn open(
+        params: &codec::Parameters,
+        in_time_base: Rational,
+        extension: &str,
+    ) -> anyhow::Result<OpenOutput> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let path = format!("recording_{}.{}", timestamp, extension);
+
+        let mut octx = format::output(&path)?;
+        {
+            let mut stream = octx.add_stream(None)?;
+            stream.set_parameters(params.clone());
+        }
+        octx.write_header()?;
+
+        let stream_index = 0;
+        let out_time_base = octx.stream(stream_index).unwrap().time_base();
+
+        println!("[Recorder] Recording started -> {}", path);
+        Ok(OpenOutput {
+            octx,
+            stream_index,
+            in_time_base,
+            out_time_base,
+        })
+    }
+
+    fn write(&mut self, packet: &Packet) {
+        let Some(out) = &mut self.output else {
+            return;
+        };
+        let mut packet = packet.clone();
+        packet.rescale_ts(out.in_time_base, out.out_time_base);
+        packet.set_stream(out.stream_index);
+        if let Err(e) = packet.write_interleaved(&mut out.octx) {
+            eprintln!("[Recorder] Failed to write packet: {:?}", e);
+        }
+    }
+
+    fn close(&mut self) {
+        if let Some(mut out) = self.output.take() {
+            if let Err(e) = out.octx.write_trailer() {
+                eprintln!("[Recorder] Failed to finalize output: {:?}", e);
+            } else {
+                println!("[Recorder] Recording stopped.");
+            }
+        }
+    }
+}
@@ -5,36 +5,134 @@ use ffmpeg_next as ffmpeg;
 use ffmpeg_next::{
     Dictionary, codec::Context as CodecContext, format::input_with_dictionary, media::Type,
 };
-use sdl2::{event::Event, keyboard::Scancode, pixels::PixelFormatEnum};
+use sdl2::{
+    event::Event,
+    keyboard::Scancode,
+    pixels::{Color, PixelFormatEnum},
+    render::{Canvas, TextureCreator},
+    ttf::Font,
+    video::{Window, WindowContext},
+};
 use std::{
     sync::{
         Arc,
-        atomic::AtomicBool,
-        mpsc::{Receiver, Sender},
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        mpsc::Sender,
     },
     thread,
-    time::Duration,
+    time::{Duration, Instant},
 };
+use tokio::sync::broadcast;
+
+use crate::config::{self, Config};
+use crate::recorder::{Recorder, RecorderToggle};
+use crate::webrtc::{self, WebrtcFeed};
+use crate::{Command, Heartbeat, create_heartbeat};
 
-use crate::{Command, ControlBits, DEFAULT_CTRL_BITS, Heartbeat, config, create_heartbeat};
+/// Toggles the stats HUD on/off. Handled directly in the event loop rather
+/// than through `config::Action`, since it's a UI-only concern with nothing
+/// to do with drone control.
+const HUD_TOGGLE_KEY: Scancode = Scancode::H;
 
-pub fn run_video_receiver(tx_cmd: Sender<Command>, running: Arc<AtomicBool>) -> anyhow::Result<()> {
+/// Turns the raw per-frame byte/count totals into a rolling one-second
+/// average, so the HUD reports FPS/bitrate rather than instantaneous noise.
+struct HudWindow {
+    window_start: Instant,
+    frames: u32,
+    bytes: usize,
+    fps: f64,
+    bitrate_kbps: f64,
+}
+
+impl HudWindow {
+    fn new() -> Self {
+        Self {
+            window_start: Instant::now(),
+            frames: 0,
+            bytes: 0,
+            fps: 0.0,
+            bitrate_kbps: 0.0,
+        }
+    }
+
+    fn record(&mut self, frame: &DecodedFrame) {
+        self.frames += 1;
+        self.bytes += frame.packet_bytes;
+
+        let elapsed = self.window_start.elapsed();
+        if elapsed >= Duration::from_secs(1) {
+            let secs = elapsed.as_secs_f64();
+            self.fps = self.frames as f64 / secs;
+            self.bitrate_kbps = (self.bytes as f64 * 8.0 / 1000.0) / secs;
+            self.frames = 0;
+            self.bytes = 0;
+            self.window_start = Instant::now();
+        }
+    }
+}
+
+/// Renders `text` as a texture in the top-left corner. A free function to
+/// avoid borrowing `canvas` and the texture creator derived from it at once.
+fn draw_hud_text(
+    canvas: &mut Canvas<Window>,
+    texture_creator: &TextureCreator<WindowContext>,
+    font: &Font,
+    text: &str,
+) -> Result<(), String> {
+    let surface = font
+        .render(text)
+        .blended(Color::RGBA(255, 255, 0, 255))
+        .map_err(|e| e.to_string())?;
+    let texture = texture_creator
+        .create_texture_from_surface(&surface)
+        .map_err(|e| e.to_string())?;
+    let query = texture.query();
+    canvas.copy(
+        &texture,
+        None,
+        sdl2::rect::Rect::new(8, 8, query.width, query.height),
+    )
+}
+
+pub fn run_video_receiver(
+    tx_cmd: Sender<Command>,
+    running: Arc<AtomicBool>,
+    cfg: Arc<Config>,
+) -> anyhow::Result<()> {
     // 1) Initialize FFmpeg
     ffmpeg::init().context("Failed to init ffmpeg")?;
 
-    // 3) Channel for decoded frames
-    let (tx, rx) = std::sync::mpsc::sync_channel::<DecodedFrame>(1);
+    // 3) Broadcast channel for decoded frames: every consumer (SDL here,
+    // plus any future recorder/network sink) gets its own subscription and
+    // drops what it can't keep up with, rather than one of them starving
+    // the others on a single depth-1 channel.
+    let (tx, mut rx) = broadcast::channel::<Arc<DecodedFrame>>(4);
+
+    // Opt-in: re-broadcast the raw H.264 feed to a WHIP endpoint so a ground
+    // station can watch it in a browser alongside this SDL window.
+    let webrtc_feed = cfg
+        .network
+        .whip_endpoint
+        .clone()
+        .map(webrtc::spawn);
+
+    // Mid-flight recording toggle, flipped by the `R` key in the event loop below.
+    let recorder_toggle = RecorderToggle::new();
+
+    // Link-quality counters for the stats HUD.
+    let stats = Arc::new(StreamStats::default());
 
     // 4) Spawn decode thread (unchanged)
-    thread::spawn(move || {
-        let mut opts = Dictionary::new();
-        opts.set("rtsp_transport", "udp");
-        opts.set("stimeout", "5000000");
-        opts.set("err_detect", "explode");
-        if let Err(e) = decode_thread(tx, opts) {
-            eprintln!("Decoder thread error: {:?}", e);
-        }
-    });
+    {
+        let recorder_toggle = recorder_toggle.clone();
+        let cfg = cfg.clone();
+        let stats = stats.clone();
+        thread::spawn(move || {
+            if let Err(e) = decode_thread(tx, webrtc_feed, recorder_toggle, cfg, stats) {
+                eprintln!("Decoder thread error: {:?}", e);
+            }
+        });
+    }
 
     // 5) Initialize SDL2
     let sdl = sdl2::init().expect("Failed to init SDL2");
@@ -57,38 +155,41 @@ pub fn run_video_receiver(tx_cmd: Sender<Command>, running: Arc<AtomicBool>) ->
     let mut width = 0;
     let mut height = 0;
 
+    // Stats HUD: loaded best-effort, since a missing font shouldn't take
+    // down the whole video loop.
+    let ttf_context = sdl2::ttf::init().ok();
+    let font: Option<Font> = ttf_context.as_ref().and_then(|ttf| {
+        ttf.load_font(&cfg.video.hud_font_path, 16)
+            .map_err(|e| {
+                eprintln!(
+                    "[HUD] Failed to load font {}: {} - HUD disabled",
+                    cfg.video.hud_font_path, e
+                )
+            })
+            .ok()
+    });
+    let mut hud_visible = font.is_some();
+    let mut hud_window = HudWindow::new();
+
     // Main loop
     while running.load(std::sync::atomic::Ordering::SeqCst) {
         // 6a) Handle SDL events (window + keyboard)
         for ev in event_pump.poll_iter() {
             match ev {
                 Event::Quit { .. } => running.store(false, std::sync::atomic::Ordering::SeqCst),
+                Event::KeyDown {
+                    scancode: Some(HUD_TOGGLE_KEY),
+                    ..
+                } => hud_visible = !hud_visible,
                 Event::KeyDown {
                     scancode: Some(sc), ..
-                } => {
-                    let mut ctrl_bits: ControlBits = DEFAULT_CTRL_BITS;
-                    match sc {
-                        Scancode::Space => ctrl_bits[4] = crate::CTRL_LAUNCH,
-                        Scancode::L => ctrl_bits[4] = crate::CTRL_LAND,
-                        Scancode::O => ctrl_bits[4] = crate::CTRL_AUX,
-                        Scancode::P => ctrl_bits[4] = crate::CTRL_PANIC,
-                        Scancode::A | Scancode::Left => {
-                            ctrl_bits[0] = ctrl_bits[0].saturating_sub(0x1d)
-                        }
-                        Scancode::D | Scancode::Right => {
-                            ctrl_bits[0] = ctrl_bits[0].saturating_add(0x1d)
-                        }
-                        Scancode::W | Scancode::Up => {
-                            ctrl_bits[1] = ctrl_bits[1].saturating_add(0x1d)
-                        }
-                        Scancode::S | Scancode::Down => {
-                            ctrl_bits[1] = ctrl_bits[1].saturating_sub(0x1d)
-                        }
-                        Scancode::Q => ctrl_bits[3] = ctrl_bits[3].saturating_add(0x1d),
-                        Scancode::E => ctrl_bits[3] = ctrl_bits[3].saturating_sub(0x1d),
-                        _ => {}
+                } => match cfg.action_for(sc) {
+                    Some(config::Action::ToggleRecording) => {
+                        let now_recording = recorder_toggle.toggle();
+                        println!("[Recorder] {}", if now_recording { "ON" } else { "OFF" });
                     }
-                    if ctrl_bits != DEFAULT_CTRL_BITS {
+                    Some(action) => {
+                        let ctrl_bits = action.apply(cfg.controls.center, cfg.controls.step);
                         let packet: Heartbeat = create_heartbeat(ctrl_bits);
                         println!(
                             "[Input] {}",
@@ -100,11 +201,12 @@ pub fn run_video_receiver(tx_cmd: Sender<Command>, running: Arc<AtomicBool>) ->
                         );
                         let _ = tx_cmd.send(Command {
                             payload: packet,
-                            duration: config::MOVEMENT_COMMAND_DUR,
+                            duration: cfg.timing.movement_command_dur(),
                             priority: 0,
                         });
                     }
-                }
+                    None => {}
+                },
                 _ => {}
             }
         }
@@ -135,86 +237,248 @@ pub fn run_video_receiver(tx_cmd: Sender<Command>, running: Arc<AtomicBool>) ->
 
                     canvas.clear();
                     canvas
-                        .copy_ex(tex, None, None, 90.0, None, false, false)
+                        .copy_ex(tex, None, None, cfg.video.rotation_deg, None, false, false)
                         .expect("Failed to copy texture");
+
+                    // Drawn after copy_ex with plain `copy`, so the HUD stays
+                    // upright regardless of the video's configured rotation.
+                    if hud_visible {
+                        if let Some(font) = &font {
+                            let latency_ms = frame.decoded_at.elapsed().as_secs_f64() * 1000.0;
+                            let text = format!(
+                                "{:.1} fps  {:.0} kbps  {:.0} ms latency  {} dropped  {} corrupt",
+                                hud_window.fps,
+                                hud_window.bitrate_kbps,
+                                latency_ms,
+                                stats.dropped_frames.load(Ordering::Relaxed),
+                                stats.corrupt_frames.load(Ordering::Relaxed),
+                            );
+                            if let Err(e) =
+                                draw_hud_text(&mut canvas, &texture_creator, font, &text)
+                            {
+                                eprintln!("[HUD] Failed to render: {}", e);
+                            }
+                        }
+                    }
+
                     canvas.present();
                 }
+
+                hud_window.record(&frame);
+            }
+            Err(broadcast::error::TryRecvError::Empty) => thread::sleep(Duration::from_millis(5)),
+            Err(broadcast::error::TryRecvError::Lagged(n)) => {
+                stats.dropped_frames.fetch_add(n, Ordering::Relaxed);
+                eprintln!("[Video] Display fell behind, dropped {} frame(s)", n);
             }
-            Err(std::sync::mpsc::TryRecvError::Empty) => thread::sleep(Duration::from_millis(5)),
-            Err(std::sync::mpsc::TryRecvError::Disconnected) => break,
+            Err(broadcast::error::TryRecvError::Closed) => break,
         }
     }
 
     Ok(())
 }
 
-/// Holds one decoded YUV420p frame.
-struct DecodedFrame {
-    width: u32,
-    height: u32,
-    y_stride: usize,
-    uv_stride: usize,
-    y_plane: Vec<u8>,
-    u_plane: Vec<u8>,
-    v_plane: Vec<u8>,
+/// Holds one decoded YUV420p frame. Wrapped in `Arc` on the broadcast channel
+/// so fanning it out to several consumers never clones the YUV planes.
+pub struct DecodedFrame {
+    pub width: u32,
+    pub height: u32,
+    pub y_stride: usize,
+    pub uv_stride: usize,
+    pub y_plane: Vec<u8>,
+    pub u_plane: Vec<u8>,
+    pub v_plane: Vec<u8>,
+    /// Encoded bytes consumed since the previous frame, for bitrate.
+    pub packet_bytes: usize,
+    /// When the decoder produced this frame. The HUD's latency figure runs
+    /// from here to display; network and decode time are not included.
+    pub decoded_at: Instant,
+}
+
+/// Link-quality counters shared between the decode thread and whichever UI
+/// is watching it, so a stats HUD can report FPS/bitrate/latency/drops
+/// without the decoder knowing anything about rendering.
+#[derive(Default)]
+pub struct StreamStats {
+    pub corrupt_frames: AtomicU64,
+    pub dropped_frames: AtomicU64,
+}
+
+/// Decode errors in a row before we reopen the RTSP session for a fresh
+/// keyframe. One bad RTP packet can corrupt a run of several frames, so we
+/// wait for a short streak rather than reacting to a single glitch.
+///
+/// We used to ask for recovery via an out-of-band RTCP PLI sent on our own
+/// socket, but that meant running a second RTSP session alongside the one
+/// `ffmpeg` itself negotiates - a PLI there can't recover a stream decoded
+/// from a different session, and a single-session drone may not tolerate two
+/// concurrent `PLAY`s at all. Reopening `ffmpeg`'s own input instead reuses
+/// its one real session: a fresh `PLAY` always starts with an I-frame.
+const RECONNECT_ERROR_THRESHOLD: u32 = 3;
+
+/// `receive_frame` returns `Other { errno: EAGAIN }` - not a real decode
+/// error - whenever the decoder simply has no frame ready yet and wants
+/// another packet first; every other `Err` it returns is a genuine
+/// corruption signal and needs to count the same as a `send_packet` error,
+/// since `err_detect=explode` often surfaces corruption here instead.
+const EAGAIN: i32 = 11;
+
+/// Minimum gap between consecutive RTSP reopens. Without this, a link that's
+/// bad enough to keep tripping `RECONNECT_ERROR_THRESHOLD` immediately after
+/// each reopen would thrash tearing the session down and back up in a tight
+/// loop; this matches the ~500ms rate limit the original RTCP PLI design
+/// called for.
+const MIN_REOPEN_INTERVAL: Duration = Duration::from_millis(500);
+
+fn opts() -> Dictionary<'static> {
+    let mut opts = Dictionary::new();
+    opts.set("rtsp_transport", "udp");
+    opts.set("stimeout", "5000000");
+    opts.set("err_detect", "explode");
+    opts
 }
 
-/// Runs in a background thread: opens RTSP, decodes frames, sends them over the channel.
+/// Runs in a background thread: opens RTSP, decodes frames, publishes them to
+/// every subscriber of the broadcast channel. Reopens the RTSP session after
+/// a run of decode errors rather than giving up.
 fn decode_thread(
-    tx: std::sync::mpsc::SyncSender<DecodedFrame>,
-    opts: Dictionary<'_>,
+    tx: broadcast::Sender<Arc<DecodedFrame>>,
+    webrtc_feed: Option<WebrtcFeed>,
+    recorder_toggle: RecorderToggle,
+    cfg: Arc<Config>,
+    stats: Arc<StreamStats>,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let uri = "rtsp://192.168.1.1:7070/webcam";
-    let mut ictx = input_with_dictionary(uri, opts)?;
+    let uri = cfg.network.rtsp_uri();
+    let mut recorder = Recorder::new(recorder_toggle, cfg.video.recording_extension.clone());
+    let mut last_reopen: Option<Instant> = None;
 
-    let input = ictx.streams().best(Type::Video).ok_or("No video stream")?;
-    let stream_index = input.index();
+    'session: loop {
+        if let Some(last) = last_reopen {
+            let elapsed = last.elapsed();
+            if elapsed < MIN_REOPEN_INTERVAL {
+                thread::sleep(MIN_REOPEN_INTERVAL - elapsed);
+            }
+        }
+        last_reopen = Some(Instant::now());
 
-    let mut decoder = CodecContext::from_parameters(input.parameters())?
-        .decoder()
-        .video()?;
+        let mut ictx = input_with_dictionary(&uri, opts())?;
 
-    for (stream, packet) in ictx.packets() {
-        if stream.index() != stream_index {
-            continue;
-        }
-        decoder.send_packet(&packet)?;
-        let mut frame = ffmpeg::util::frame::video::Video::empty();
-        while decoder.receive_frame(&mut frame).is_ok() {
-            let (w, h) = (frame.width(), frame.height());
-            let y_stride = frame.stride(0);
-            let uv_stride = frame.stride(1);
-            let y_size = (y_stride as i32 * h as i32) as usize;
-            let uv_size = (uv_stride as i32 * (h as i32 / 2)) as usize;
-
-            let mut y_plane = vec![0u8; y_size];
-            let mut u_plane = vec![0u8; uv_size];
-            let mut v_plane = vec![0u8; uv_size];
-
-            for row in 0..h as usize {
-                let src = &frame.data(0)[row * y_stride as usize..][..w as usize];
-                let dst = &mut y_plane[row * y_stride as usize..][..w as usize];
-                dst.copy_from_slice(src);
+        let input = ictx.streams().best(Type::Video).ok_or("No video stream")?;
+        let stream_index = input.index();
+        let time_base = input.time_base();
+        let input_params = input.parameters();
+
+        let mut decoder = CodecContext::from_parameters(input.parameters())?
+            .decoder()
+            .video()?;
+        let mut consecutive_errors: u32 = 0;
+        let mut packet_bytes: usize = 0;
+
+        for (stream, packet) in ictx.packets() {
+            if stream.index() != stream_index {
+                continue;
             }
-            for row in 0..(h as usize / 2) {
-                let src_u = &frame.data(1)[row * uv_stride as usize..][..(w as usize / 2)];
-                let dst_u = &mut u_plane[row * uv_stride as usize..][..(w as usize / 2)];
-                dst_u.copy_from_slice(src_u);
-                let src_v = &frame.data(2)[row * uv_stride as usize..][..(w as usize / 2)];
-                let dst_v = &mut v_plane[row * uv_stride as usize..][..(w as usize / 2)];
-                dst_v.copy_from_slice(src_v);
+
+            packet_bytes += packet.size();
+
+            if let Some(feed) = &webrtc_feed {
+                if let Some(data) = packet.data() {
+                    let duration = (packet.duration() as f64 * time_base.numerator() as f64
+                        / time_base.denominator() as f64)
+                        .max(0.0);
+                    feed.feed(
+                        data.to_vec(),
+                        Duration::from_secs_f64(duration),
+                        packet.is_key(),
+                    );
+                }
             }
 
-            let _ = tx.try_send(DecodedFrame {
-                width: w,
-                height: h,
-                y_stride: y_stride as usize,
-                uv_stride: uv_stride as usize,
-                y_plane,
-                u_plane,
-                v_plane,
-            });
+            // Parallel sink: muxed straight through, independent of decoding below.
+            recorder.feed(&packet, &input_params, time_base);
+
+            if let Err(e) = decoder.send_packet(&packet) {
+                consecutive_errors += 1;
+                stats.corrupt_frames.fetch_add(1, Ordering::Relaxed);
+                eprintln!("[Video] Decode error ({}): {:?}", consecutive_errors, e);
+                if consecutive_errors >= RECONNECT_ERROR_THRESHOLD {
+                    eprintln!(
+                        "[Video] {} consecutive decode errors, reopening RTSP session for a fresh keyframe",
+                        consecutive_errors
+                    );
+                    continue 'session;
+                }
+                continue;
+            }
+            consecutive_errors = 0;
+
+            let mut frame = ffmpeg::util::frame::video::Video::empty();
+            loop {
+                match decoder.receive_frame(&mut frame) {
+                    Err(ffmpeg::Error::Other { errno }) if errno == EAGAIN => break,
+                    Err(e) => {
+                        consecutive_errors += 1;
+                        stats.corrupt_frames.fetch_add(1, Ordering::Relaxed);
+                        eprintln!("[Video] Decode error ({}): {:?}", consecutive_errors, e);
+                        if consecutive_errors >= RECONNECT_ERROR_THRESHOLD {
+                            eprintln!(
+                                "[Video] {} consecutive decode errors, reopening RTSP session for a fresh keyframe",
+                                consecutive_errors
+                            );
+                            continue 'session;
+                        }
+                        break;
+                    }
+                    Ok(()) => {
+                        consecutive_errors = 0;
+                    }
+                }
+
+                let (w, h) = (frame.width(), frame.height());
+                let y_stride = frame.stride(0);
+                let uv_stride = frame.stride(1);
+                let y_size = (y_stride as i32 * h as i32) as usize;
+                let uv_size = (uv_stride as i32 * (h as i32 / 2)) as usize;
+
+                let mut y_plane = vec![0u8; y_size];
+                let mut u_plane = vec![0u8; uv_size];
+                let mut v_plane = vec![0u8; uv_size];
+
+                for row in 0..h as usize {
+                    let src = &frame.data(0)[row * y_stride as usize..][..w as usize];
+                    let dst = &mut y_plane[row * y_stride as usize..][..w as usize];
+                    dst.copy_from_slice(src);
+                }
+                for row in 0..(h as usize / 2) {
+                    let src_u = &frame.data(1)[row * uv_stride as usize..][..(w as usize / 2)];
+                    let dst_u = &mut u_plane[row * uv_stride as usize..][..(w as usize / 2)];
+                    dst_u.copy_from_slice(src_u);
+                    let src_v = &frame.data(2)[row * uv_stride as usize..][..(w as usize / 2)];
+                    let dst_v = &mut v_plane[row * uv_stride as usize..][..(w as usize / 2)];
+                    dst_v.copy_from_slice(src_v);
+                }
+
+                // `send` never blocks: a full ring buffer just overwrites the
+                // oldest unread frame, and `Err` here only means no one is
+                // subscribed right now - both are fine to ignore.
+                let _ = tx.send(Arc::new(DecodedFrame {
+                    width: w,
+                    height: h,
+                    y_stride: y_stride as usize,
+                    uv_stride: uv_stride as usize,
+                    y_plane,
+                    u_plane,
+                    v_plane,
+                    packet_bytes,
+                    decoded_at: Instant::now(),
+                }));
+                packet_bytes = 0;
+            }
         }
+
+        // The drone closed the stream on its own; nothing left to recover.
+        break;
     }
+    recorder.shutdown();
     Ok(())
 }
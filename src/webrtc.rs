@@ -0,0 +1,150 @@
+// src/webrtc.rs
+//
+// Optional WHIP/WebRTC relay: tees the raw H.264 access units coming off the
+// RTSP feed to a browser-reachable peer so a ground station can watch the
+// first-person view without the SDL window.
+
+use std::{
+    sync::{
+        Arc,
+        mpsc::{Receiver, SyncSender, TrySendError},
+    },
+    time::Duration,
+};
+
+use webrtc::{
+    api::{APIBuilder, media_engine::MIME_TYPE_H264, media_engine::MediaEngine},
+    media::Sample,
+    peer_connection::{
+        configuration::RTCConfiguration, sdp::session_description::RTCSessionDescription,
+    },
+    rtp_transceiver::rtp_codec::RTCRtpCodecCapability,
+    track::track_local::{TrackLocal, TrackLocalWriter, track_local_static_sample::TrackLocalStaticSample},
+};
+
+/// One H.264 access unit pulled off the decode thread, before it ever reaches the decoder.
+struct EncodedUnit {
+    data: Vec<u8>,
+    duration: Duration,
+    is_key: bool,
+}
+
+/// Handle held by `decode_thread`. Feeding is non-blocking: if the relay task
+/// falls behind, samples are dropped rather than stalling decode, just as the
+/// decoded-frame broadcast channel never blocks the decoder.
+#[derive(Clone)]
+pub struct WebrtcFeed {
+    tx: SyncSender<EncodedUnit>,
+}
+
+impl WebrtcFeed {
+    pub fn feed(&self, data: Vec<u8>, duration: Duration, is_key: bool) {
+        match self.tx.try_send(EncodedUnit {
+            data,
+            duration,
+            is_key,
+        }) {
+            Ok(()) | Err(TrySendError::Full(_)) | Err(TrySendError::Disconnected(_)) => {}
+        }
+    }
+}
+
+/// Spawns the WHIP session on its own thread (with its own single-threaded
+/// tokio runtime, since the decode thread is plain `std::thread`) and returns
+/// a handle the decode thread can push raw access units into.
+pub fn spawn(whip_url: String) -> WebrtcFeed {
+    let (tx, rx) = std::sync::mpsc::sync_channel(32);
+    std::thread::spawn(move || {
+        let rt = match tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+        {
+            Ok(rt) => rt,
+            Err(e) => {
+                eprintln!("[WebRTC] Failed to start relay runtime: {:?}", e);
+                return;
+            }
+        };
+        if let Err(e) = rt.block_on(run(whip_url, rx)) {
+            eprintln!("[WebRTC] Relay error: {:?}", e);
+        }
+    });
+    WebrtcFeed { tx }
+}
+
+async fn run(whip_url: String, rx: Receiver<EncodedUnit>) -> anyhow::Result<()> {
+    let mut media_engine = MediaEngine::default();
+    media_engine.register_default_codecs()?;
+    let api = APIBuilder::new().with_media_engine(media_engine).build();
+
+    let pc = Arc::new(
+        api.new_peer_connection(RTCConfiguration::default())
+            .await?,
+    );
+
+    let track = Arc::new(TrackLocalStaticSample::new(
+        RTCRtpCodecCapability {
+            mime_type: MIME_TYPE_H264.to_owned(),
+            ..Default::default()
+        },
+        "video".to_owned(),
+        "drctr".to_owned(),
+    ));
+    pc.add_track(Arc::clone(&track) as Arc<dyn TrackLocal + Send + Sync>)
+        .await?;
+
+    let offer = pc.create_offer(None).await?;
+
+    // A non-trickle WHIP server only gets one shot at the offer, so it needs
+    // every ICE candidate baked in - wait for gathering to finish before
+    // reading back the local description we actually send.
+    let mut gather_complete = pc.gathering_complete_promise().await;
+    pc.set_local_description(offer).await?;
+    let _ = gather_complete.recv().await;
+
+    let local_desc = pc
+        .local_description()
+        .await
+        .ok_or_else(|| anyhow::anyhow!("no local description after ICE gathering"))?;
+
+    let answer_sdp = post_whip_offer(&whip_url, &local_desc.sdp).await?;
+    pc.set_remote_description(RTCSessionDescription::answer(answer_sdp)?)
+        .await?;
+
+    println!("[WebRTC] WHIP session established with {}", whip_url);
+
+    // Only start writing samples once the first keyframe is seen, so a
+    // newly-joined browser never opens on a half-decodable picture.
+    let mut started = false;
+    while let Ok(unit) = rx.recv() {
+        if !started {
+            if !unit.is_key {
+                continue;
+            }
+            started = true;
+        }
+        let sample = Sample {
+            data: unit.data.into(),
+            duration: unit.duration,
+            ..Default::default()
+        };
+        if let Err(e) = track.write_sample(&sample).await {
+            eprintln!("[WebRTC] Failed to write sample: {:?}", e);
+        }
+    }
+
+    Ok(())
+}
+
+/// POSTs the SDP offer to the WHIP endpoint and returns the answer SDP body.
+async fn post_whip_offer(whip_url: &str, offer_sdp: &str) -> anyhow::Result<String> {
+    let client = reqwest::Client::new();
+    let resp = client
+        .post(whip_url)
+        .header("Content-Type", "application/sdp")
+        .body(offer_sdp.to_owned())
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(resp.text().await?)
+}